@@ -0,0 +1,82 @@
+use std::io;
+
+use anyhow::Result;
+
+use crate::codec::BlockCompressor;
+
+const BROTLI_BUFFER_SIZE: usize = 4096;
+const BROTLI_QUALITY: u32 = 9;
+const BROTLI_LG_WINDOW_SIZE: u32 = 22;
+
+/// Brotli codec backend: slower to encode than [`crate::ZstdWrapper`], smaller
+/// output.
+pub struct BrotliWrapper {
+    buffer: Vec<u8>,
+}
+
+impl BrotliWrapper {
+    pub fn new() -> Self {
+        Self { buffer: Vec::new() }
+    }
+
+    pub fn compress(&mut self, bytes: &[u8]) -> Result<&[u8]> {
+        self.buffer.truncate(0);
+        let mut wrapper = io::Cursor::new(bytes);
+        let mut output_wrapper = io::Cursor::new(&mut self.buffer);
+
+        let mut encoder = ::brotli::CompressorWriter::new(
+            &mut output_wrapper,
+            BROTLI_BUFFER_SIZE,
+            BROTLI_QUALITY,
+            BROTLI_LG_WINDOW_SIZE,
+        );
+        io::copy(&mut wrapper, &mut encoder)?;
+        drop(encoder);
+
+        let out_pos = output_wrapper.position() as usize;
+        drop(output_wrapper);
+        Ok(&self.buffer[0..out_pos])
+    }
+
+    pub fn decompress(&mut self, bytes: &[u8]) -> Result<&[u8]> {
+        self.buffer.truncate(0);
+        let mut output_wrapper = io::Cursor::new(&mut self.buffer);
+
+        let mut decoder = ::brotli::Decompressor::new(bytes, BROTLI_BUFFER_SIZE);
+        io::copy(&mut decoder, &mut output_wrapper)?;
+
+        let out_pos = output_wrapper.position() as usize;
+        drop(output_wrapper);
+        Ok(&self.buffer[0..out_pos])
+    }
+}
+
+impl Default for BrotliWrapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockCompressor for BrotliWrapper {
+    fn compress(&mut self, bytes: &[u8]) -> Result<&[u8]> {
+        BrotliWrapper::compress(self, bytes)
+    }
+
+    fn decompress(&mut self, bytes: &[u8]) -> Result<&[u8]> {
+        BrotliWrapper::decompress(self, bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BrotliWrapper;
+
+    #[test]
+    fn test_encode() {
+        let mut encoder = BrotliWrapper::new();
+        let input = b"asasaasasasasasasasasasaaaaaaaaaaaaasassas";
+        let res = encoder.compress(&input[..]).unwrap().to_vec();
+        let got = encoder.decompress(&res).unwrap();
+        assert_eq!(got, input);
+    }
+}
@@ -0,0 +1,128 @@
+use anyhow::{anyhow, Result};
+
+/// Common interface implemented by every codec backend in this crate, so a call
+/// site can depend on a single trait object instead of hard-coding
+/// [`crate::ZstdWrapper`].
+pub trait BlockCompressor {
+    fn compress(&mut self, bytes: &[u8]) -> Result<&[u8]>;
+    fn decompress(&mut self, bytes: &[u8]) -> Result<&[u8]>;
+}
+
+/// One-byte tag identifying which codec produced a block, meant to be prepended
+/// to a compressed payload so a decompressor can auto-dispatch regardless of
+/// which backend was used to produce it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Codec {
+    Zstd = 0,
+    #[cfg(feature = "lz4")]
+    Lz4 = 1,
+    #[cfg(feature = "brotli")]
+    Brotli = 2,
+    Fsst = 3,
+}
+
+impl Codec {
+    pub fn tag(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(Codec::Zstd),
+            #[cfg(feature = "lz4")]
+            1 => Some(Codec::Lz4),
+            #[cfg(feature = "brotli")]
+            2 => Some(Codec::Brotli),
+            3 => Some(Codec::Fsst),
+            _ => None,
+        }
+    }
+}
+
+/// Prepends `codec`'s one-byte tag to `payload`, e.g. before writing a
+/// compressed block to a socket or file so a decoder can auto-dispatch
+/// regardless of which backend produced it.
+pub fn prepend_tag(codec: Codec, payload: &[u8]) -> Vec<u8> {
+    let mut tagged = Vec::with_capacity(1 + payload.len());
+    tagged.push(codec.tag());
+    tagged.extend_from_slice(payload);
+    tagged
+}
+
+/// Splits a tag-prefixed block produced by [`prepend_tag`] back into its codec
+/// and payload, so a single call site can auto-dispatch to the right
+/// [`BlockCompressor`] regardless of which backend produced the block.
+pub fn split_tag(bytes: &[u8]) -> Result<(Codec, &[u8])> {
+    let (&tag, payload) = bytes
+        .split_first()
+        .ok_or_else(|| anyhow!("empty tagged block"))?;
+    let codec = Codec::from_tag(tag).ok_or_else(|| anyhow!("unknown codec tag {tag}"))?;
+    Ok((codec, payload))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ZstdWrapper;
+
+    #[test]
+    fn test_tag_roundtrip() {
+        assert_eq!(Codec::from_tag(Codec::Zstd.tag()), Some(Codec::Zstd));
+        #[cfg(feature = "lz4")]
+        assert_eq!(Codec::from_tag(Codec::Lz4.tag()), Some(Codec::Lz4));
+        #[cfg(feature = "brotli")]
+        assert_eq!(Codec::from_tag(Codec::Brotli.tag()), Some(Codec::Brotli));
+        assert_eq!(Codec::from_tag(Codec::Fsst.tag()), Some(Codec::Fsst));
+    }
+
+    #[test]
+    fn test_from_tag_unknown() {
+        assert_eq!(Codec::from_tag(0xAB), None);
+    }
+
+    #[test]
+    fn test_prepend_split_dispatch() {
+        let mut encoder = ZstdWrapper::new();
+        let input = b"asasaasasasasasasasasasaaaaaaaaaaaaasassas";
+        let compressed = encoder.compress(&input[..]).unwrap().to_vec();
+        let tagged = prepend_tag(Codec::Zstd, &compressed);
+
+        let (codec, payload) = split_tag(&tagged).unwrap();
+        assert_eq!(codec, Codec::Zstd);
+
+        let decompressed = match codec {
+            Codec::Zstd => encoder.decompress(payload).unwrap().to_vec(),
+            _ => unreachable!("only Zstd blocks were tagged in this test"),
+        };
+        assert_eq!(decompressed, input);
+    }
+
+    #[test]
+    fn test_split_tag_rejects_empty() {
+        assert!(split_tag(&[]).is_err());
+    }
+
+    #[test]
+    fn test_prepend_split_dispatch_fsst() {
+        use crate::fsst::FsstWrapper;
+
+        let mut trained =
+            FsstWrapper::with_symbols(vec![b"as".to_vec(), b"sas".to_vec()]).unwrap();
+        let input = b"asasaasasasasasasasasasaaaaaaaaaaaaasassas";
+        let compressed = trained.compress(&input[..]).unwrap().to_vec();
+        let tagged = prepend_tag(Codec::Fsst, &compressed);
+
+        let (codec, payload) = split_tag(&tagged).unwrap();
+        assert_eq!(codec, Codec::Fsst);
+
+        // The table travels with the block, so a decoder that never saw
+        // `trained`'s table can still decode it.
+        let mut fresh = FsstWrapper::with_symbols(Vec::new()).unwrap();
+        let decompressed = match codec {
+            Codec::Fsst => fresh.decompress(payload).unwrap().to_vec(),
+            _ => unreachable!("only Fsst blocks were tagged in this test"),
+        };
+        assert_eq!(decompressed, input);
+    }
+}
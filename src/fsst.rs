@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+
+use crate::codec::BlockCompressor;
+
+const MAX_SYMBOLS: usize = 255;
+const MAX_SYMBOL_LEN: usize = 8;
+const TRAINING_PASSES: usize = 5;
+const ESCAPE: u8 = 0xFF;
+
+/// Max number of leading bytes of a symbol used as its hash bucket key. Symbols
+/// shorter than this are keyed on their full (unpadded) length so a short symbol's
+/// bucket key matches exactly what `compress` looks up for a short remaining input
+/// — padding short keys with zero bytes would only match input that happens to be
+/// followed by real zero bytes.
+const INDEX_KEY_LEN: usize = 3;
+
+/// Fast Static Symbol Table codec (see the FSST paper) for very small blocks
+/// where a full zstd frame's dictionary/header overhead would exceed the payload
+/// itself. [`FsstWrapper::compress`] prepends the serialized symbol table ahead
+/// of the codes, so the block is self-describing: [`FsstWrapper::decompress`]
+/// reads the table straight out of the block and needs no out-of-band state,
+/// which is what makes `Codec::Fsst` safe to dispatch on alongside the other
+/// backends in [`crate::codec`].
+pub struct FsstWrapper {
+    symbols: Vec<Vec<u8>>,
+    index: HashMap<Vec<u8>, Vec<u8>>,
+    buffer: Vec<u8>,
+}
+
+impl FsstWrapper {
+    /// Greedily builds a symbol table of up to 255 symbols (1 to 8 bytes each)
+    /// from a corpus of samples. Each pass counts how often the current table's
+    /// symbols -- and concatenations of adjacent symbols -- occur in the corpus,
+    /// then keeps the candidates with the highest `(len - 1) * frequency` gain.
+    pub fn train(samples: &[&[u8]]) -> Self {
+        let mut symbols: Vec<Vec<u8>> = Vec::new();
+        for _ in 0..TRAINING_PASSES {
+            let mut counts: HashMap<Vec<u8>, usize> = HashMap::new();
+            for sample in samples {
+                let mut pos = 0;
+                while pos < sample.len() {
+                    let (symbol, len) = Self::longest_match(&symbols, &sample[pos..]);
+                    *counts.entry(symbol.clone()).or_insert(0) += 1;
+
+                    if pos + len < sample.len() {
+                        let (next, next_len) = Self::longest_match(&symbols, &sample[pos + len..]);
+                        if len + next_len <= MAX_SYMBOL_LEN {
+                            let mut merged = symbol;
+                            merged.extend_from_slice(&next);
+                            *counts.entry(merged).or_insert(0) += 1;
+                        }
+                    }
+                    pos += len;
+                }
+            }
+
+            let mut candidates: Vec<(Vec<u8>, usize)> = counts
+                .into_iter()
+                .filter(|(symbol, _)| !symbol.is_empty() && symbol.len() <= MAX_SYMBOL_LEN)
+                .collect();
+            candidates.sort_unstable_by_key(|(symbol, freq)| {
+                std::cmp::Reverse((symbol.len() - 1) * freq)
+            });
+
+            symbols = candidates
+                .into_iter()
+                .map(|(symbol, _)| symbol)
+                .take(MAX_SYMBOLS)
+                .collect();
+        }
+
+        Self::with_symbols(symbols).expect("training never produces more than MAX_SYMBOLS symbols")
+    }
+
+    /// Rebuilds a wrapper from a previously trained table, e.g. one decoded with
+    /// [`FsstWrapper::from_serialized_table`]. Errors if `symbols` holds more than
+    /// [`MAX_SYMBOLS`] entries, since codes are encoded as a single byte and code
+    /// 255 collides with [`ESCAPE`].
+    pub fn with_symbols(symbols: Vec<Vec<u8>>) -> Result<Self> {
+        if symbols.len() > MAX_SYMBOLS {
+            return Err(anyhow!(
+                "fsst table has {} symbols, at most {MAX_SYMBOLS} are supported",
+                symbols.len()
+            ));
+        }
+
+        let mut index: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+        for (code, symbol) in symbols.iter().enumerate() {
+            index.entry(Self::hash_key(symbol)).or_default().push(code as u8);
+        }
+        for codes in index.values_mut() {
+            codes.sort_unstable_by_key(|&code| std::cmp::Reverse(symbols[code as usize].len()));
+        }
+        Ok(Self {
+            symbols,
+            index,
+            buffer: Vec::new(),
+        })
+    }
+
+    pub fn symbols(&self) -> &[Vec<u8>] {
+        &self.symbols
+    }
+
+    /// Serializes the symbol table as `[count][len, bytes...]*` so it can be
+    /// stored alongside or ahead of the compressed payload.
+    pub fn serialize_table(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(1 + self.symbols.len() * (1 + MAX_SYMBOL_LEN));
+        out.push(self.symbols.len() as u8);
+        for symbol in &self.symbols {
+            out.push(symbol.len() as u8);
+            out.extend_from_slice(symbol);
+        }
+        out
+    }
+
+    /// Rebuilds a wrapper from a table serialized with
+    /// [`FsstWrapper::serialize_table`].
+    pub fn from_serialized_table(bytes: &[u8]) -> Result<Self> {
+        let (symbols, _) = Self::parse_table(bytes)?;
+        Self::with_symbols(symbols)
+    }
+
+    /// Parses a `[count][len, bytes...]*` table off the front of `bytes`,
+    /// returning the symbols and the number of bytes consumed so the caller can
+    /// locate the codes that follow. Shared by [`FsstWrapper::from_serialized_table`]
+    /// and [`FsstWrapper::decompress`], which reads the table embedded ahead of a
+    /// self-describing block.
+    fn parse_table(bytes: &[u8]) -> Result<(Vec<Vec<u8>>, usize)> {
+        let mut pos = 0;
+        let count = *bytes.get(pos).ok_or_else(|| anyhow!("empty fsst table"))? as usize;
+        pos += 1;
+
+        let mut symbols = Vec::with_capacity(count);
+        for _ in 0..count {
+            let len = *bytes
+                .get(pos)
+                .ok_or_else(|| anyhow!("truncated fsst table"))? as usize;
+            pos += 1;
+            let symbol = bytes
+                .get(pos..pos + len)
+                .ok_or_else(|| anyhow!("truncated fsst table"))?
+                .to_vec();
+            pos += len;
+            symbols.push(symbol);
+        }
+        Ok((symbols, pos))
+    }
+
+    /// Bucket key for a symbol or an input position: the first `min(len,
+    /// INDEX_KEY_LEN)` bytes, unpadded. Keeping the key's length tied to the
+    /// available bytes (instead of zero-padding it to a fixed size) means the key
+    /// built for a short symbol at table-build time is exactly reproduced by the
+    /// key built from a short remaining input at compress time.
+    fn hash_key(bytes: &[u8]) -> Vec<u8> {
+        let len = bytes.len().min(INDEX_KEY_LEN);
+        bytes[..len].to_vec()
+    }
+
+    /// Linear longest-match used only during training, where the table is still
+    /// small and being rebuilt from scratch every pass.
+    fn longest_match(symbols: &[Vec<u8>], input: &[u8]) -> (Vec<u8>, usize) {
+        let mut best: Option<&[u8]> = None;
+        for symbol in symbols {
+            if symbol.len() <= input.len()
+                && &input[..symbol.len()] == symbol.as_slice()
+                && best.map_or(true, |b| symbol.len() > b.len())
+            {
+                best = Some(symbol);
+            }
+        }
+        match best {
+            Some(symbol) => (symbol.to_vec(), symbol.len()),
+            None => (vec![input[0]], 1),
+        }
+    }
+
+    /// Scans the input and at each position finds the longest symbol matching via
+    /// the hash index keyed on the next few bytes, emitting that symbol's
+    /// 1-byte code. On no match it emits an escape byte followed by the literal.
+    /// The serialized symbol table is prepended ahead of the codes so the block
+    /// is self-describing and [`FsstWrapper::decompress`] can decode it without
+    /// having been trained on the same table.
+    pub fn compress(&mut self, input: &[u8]) -> Result<&[u8]> {
+        let table = self.serialize_table();
+        self.buffer.truncate(0);
+        self.buffer.extend_from_slice(&table);
+
+        let mut pos = 0;
+        while pos < input.len() {
+            let remaining = input.len() - pos;
+            let max_key_len = remaining.min(INDEX_KEY_LEN);
+
+            // A symbol's bucket key is the unpadded prefix of its own bytes (see
+            // `hash_key`), so a bucket of key length `k` only ever holds symbols
+            // of length `k` or, for `k == INDEX_KEY_LEN`, length >= k. Trying the
+            // longest key first still finds the longest match: the long-key
+            // bucket is tried before we ever fall back to the shorter ones.
+            let matched = (1..=max_key_len).rev().find_map(|key_len| {
+                let candidates = self.index.get(&input[pos..pos + key_len])?;
+                candidates.iter().find_map(|&code| {
+                    let symbol = &self.symbols[code as usize];
+                    let len = symbol.len();
+                    if len <= remaining && &input[pos..pos + len] == symbol.as_slice() {
+                        Some((code, len))
+                    } else {
+                        None
+                    }
+                })
+            });
+
+            match matched {
+                Some((code, len)) => {
+                    self.buffer.push(code);
+                    pos += len;
+                }
+                None => {
+                    self.buffer.push(ESCAPE);
+                    self.buffer.push(input[pos]);
+                    pos += 1;
+                }
+            }
+        }
+        Ok(&self.buffer)
+    }
+
+    /// Reads the symbol table embedded ahead of the codes by
+    /// [`FsstWrapper::compress`], then for each code copies the literal after an
+    /// escape byte or expands the matching table entry. Decodes entirely from the
+    /// block's own embedded table, so `self`'s trained table is irrelevant here --
+    /// any `FsstWrapper` can decompress a block produced by any other.
+    pub fn decompress(&mut self, input: &[u8]) -> Result<&[u8]> {
+        let (symbols, table_len) = Self::parse_table(input)?;
+        let codes = &input[table_len..];
+
+        self.buffer.truncate(0);
+        let mut pos = 0;
+        while pos < codes.len() {
+            let code = codes[pos];
+            pos += 1;
+            if code == ESCAPE {
+                let byte = *codes
+                    .get(pos)
+                    .ok_or_else(|| anyhow!("truncated fsst escape sequence"))?;
+                self.buffer.push(byte);
+                pos += 1;
+            } else {
+                let symbol = symbols
+                    .get(code as usize)
+                    .ok_or_else(|| anyhow!("unknown fsst symbol code {code}"))?;
+                self.buffer.extend_from_slice(symbol);
+            }
+        }
+        Ok(&self.buffer)
+    }
+}
+
+impl BlockCompressor for FsstWrapper {
+    fn compress(&mut self, bytes: &[u8]) -> Result<&[u8]> {
+        FsstWrapper::compress(self, bytes)
+    }
+
+    fn decompress(&mut self, bytes: &[u8]) -> Result<&[u8]> {
+        FsstWrapper::decompress(self, bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FsstWrapper;
+
+    #[test]
+    fn test_roundtrip() {
+        let samples: Vec<&[u8]> = vec![
+            b"asasaasasasasasasasasasaaaaaaaaaaaaasassas",
+            b"asasaasasasasasasasasasbbbbbbbbbbbbasassas",
+            b"asasaasasasasasasasasasccccccccccccasassas",
+        ];
+        let mut codec = FsstWrapper::train(&samples);
+
+        let input = b"asasaasasasasasasasasasddddddddddddasassas";
+        let compressed = codec.compress(&input[..]).unwrap().to_vec();
+        let decompressed = codec.decompress(&compressed).unwrap();
+
+        assert_eq!(decompressed, input);
+    }
+
+    #[test]
+    fn test_roundtrip_empty_table() {
+        let mut codec = FsstWrapper::with_symbols(Vec::new()).unwrap();
+        let input = b"hello world";
+        let compressed = codec.compress(&input[..]).unwrap().to_vec();
+        // 1-byte empty table header, then an escape + literal per input byte.
+        assert_eq!(compressed.len(), 1 + input.len() * 2);
+
+        let decompressed = codec.decompress(&compressed).unwrap();
+        assert_eq!(decompressed, input);
+    }
+
+    #[test]
+    fn test_serialize_table_roundtrip() {
+        let samples: Vec<&[u8]> = vec![b"asasaasasasasasasasasasaaaaaaaaaaaaasassas"];
+        let trained = FsstWrapper::train(&samples);
+
+        let serialized = trained.serialize_table();
+        let restored = FsstWrapper::from_serialized_table(&serialized).unwrap();
+
+        assert_eq!(trained.symbols(), restored.symbols());
+    }
+
+    #[test]
+    fn test_short_symbols_are_emitted() {
+        // A 1-byte symbol followed by a non-zero input byte, so a bucket key
+        // padded with zeroes would miss it, and a 2-byte symbol that ends the input.
+        let mut codec =
+            FsstWrapper::with_symbols(vec![b"a".to_vec(), b"bc".to_vec()]).unwrap();
+
+        let input = b"abc";
+        let table = codec.serialize_table();
+        let compressed = codec.compress(&input[..]).unwrap().to_vec();
+        // table header, then "a" -> code 0, "bc" -> code 1, no escapes needed.
+        let mut expected = table;
+        expected.extend_from_slice(&[0u8, 1u8]);
+        assert_eq!(compressed, expected);
+
+        let decompressed = codec.decompress(&compressed).unwrap();
+        assert_eq!(decompressed, input);
+    }
+
+    #[test]
+    fn test_decompress_does_not_need_matching_table() {
+        let mut trained =
+            FsstWrapper::with_symbols(vec![b"a".to_vec(), b"bc".to_vec()]).unwrap();
+        let input = b"abc";
+        let compressed = trained.compress(&input[..]).unwrap().to_vec();
+
+        // A wrapper with no trained table at all can still decode the block,
+        // since the table travels with it.
+        let mut untrained = FsstWrapper::with_symbols(Vec::new()).unwrap();
+        let decompressed = untrained.decompress(&compressed).unwrap();
+        assert_eq!(decompressed, input);
+    }
+
+    #[test]
+    fn test_with_symbols_rejects_too_many() {
+        let symbols: Vec<Vec<u8>> = (0..=255u16).map(|i| vec![i as u8, (i >> 8) as u8]).collect();
+        assert!(FsstWrapper::with_symbols(symbols).is_err());
+    }
+}
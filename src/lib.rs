@@ -2,6 +2,20 @@ use std::io;
 
 use anyhow::Result;
 
+mod codec;
+#[cfg(feature = "brotli")]
+mod brotli;
+mod fsst;
+#[cfg(feature = "lz4")]
+mod lz4;
+
+pub use self::codec::{prepend_tag, split_tag, BlockCompressor, Codec};
+#[cfg(feature = "brotli")]
+pub use self::brotli::BrotliWrapper;
+pub use self::fsst::FsstWrapper;
+#[cfg(feature = "lz4")]
+pub use self::lz4::Lz4Wrapper;
+
 pub struct ZstdWrapper {
     d_dict: zstd::dict::DecoderDictionary<'static>,
     c_dict: zstd::dict::EncoderDictionary<'static>,
@@ -15,8 +29,15 @@ impl ZstdWrapper {
     }
 
     pub fn with_level(level: i32) -> Self {
-        let d_dict = zstd::dict::DecoderDictionary::copy(include_bytes!("../dictionary"));
-        let c_dict = zstd::dict::EncoderDictionary::copy(include_bytes!("../dictionary"), level);
+        Self::with_dictionary(include_bytes!("../dictionary"), level)
+    }
+
+    /// Builds the compressor/decompressor dictionaries from an arbitrary buffer
+    /// instead of the baked-in `dictionary` file, so a dictionary trained with
+    /// [`ZstdWrapper::train_dictionary`] can be plugged in without recompiling.
+    pub fn with_dictionary(dict: &[u8], level: i32) -> Self {
+        let d_dict = zstd::dict::DecoderDictionary::copy(dict);
+        let c_dict = zstd::dict::EncoderDictionary::copy(dict, level);
         Self {
             c_dict,
             d_dict,
@@ -24,12 +45,25 @@ impl ZstdWrapper {
         }
     }
 
+    /// Trains a new zstd dictionary from a corpus of samples (e.g. a batch of raw
+    /// TON blocks), mirroring the `zstd` crate's own dictionary trainer. The result
+    /// can be fed into [`ZstdWrapper::with_dictionary`] or persisted to disk to
+    /// replace the shipped `dictionary` blob, letting operators retrain on their own
+    /// block stream and A/B new dictionaries.
+    pub fn train_dictionary(samples: &[&[u8]], dict_size: usize) -> Result<Vec<u8>> {
+        Ok(zstd::dict::from_samples(samples, dict_size)?)
+    }
+
     pub fn compress(&mut self, bytes: &[u8]) -> Result<&[u8]> {
         let mut wrapper = io::Cursor::new(bytes);
         let mut output_wrapper = io::Cursor::new(&mut self.buffer);
 
         let mut encoder =
             zstd::stream::Encoder::with_prepared_dictionary(&mut output_wrapper, &self.c_dict)?;
+        // Pledging the source size writes it into the frame header, so a later
+        // `decompress`/`decompress_owned` call can preallocate exactly instead of
+        // falling back to a growth heuristic.
+        encoder.set_pledged_src_size(Some(bytes.len() as u64))?;
         io::copy(&mut wrapper, &mut encoder)?;
         encoder.finish()?;
         let out_pos = output_wrapper.position() as usize;
@@ -43,6 +77,7 @@ impl ZstdWrapper {
 
         let mut encoder =
             zstd::stream::Encoder::with_prepared_dictionary(&mut output_wrapper, &self.c_dict)?;
+        encoder.set_pledged_src_size(Some(bytes.len() as u64))?;
         io::copy(&mut wrapper, &mut encoder)?;
         encoder.finish()?;
         Ok(out_buffer)
@@ -50,7 +85,7 @@ impl ZstdWrapper {
 
     pub fn decompress_owned(&self, bytes: &[u8]) -> Result<Vec<u8>> {
         let mut wrapper = io::Cursor::new(bytes);
-        let mut out_buffer = Vec::with_capacity(bytes.len());
+        let mut out_buffer = Vec::with_capacity(Self::decompressed_capacity_hint(bytes));
         let mut output_wrapper = io::Cursor::new(&mut out_buffer);
 
         let mut decoder =
@@ -61,6 +96,7 @@ impl ZstdWrapper {
 
     pub fn decompress(&mut self, bytes: &[u8]) -> Result<&[u8]> {
         self.buffer.truncate(0);
+        self.buffer.reserve(Self::decompressed_capacity_hint(bytes));
         let mut wrapper = io::Cursor::new(bytes);
         let mut output_wrapper = io::Cursor::new(&mut self.buffer);
 
@@ -72,6 +108,75 @@ impl ZstdWrapper {
         drop(output_wrapper);
         Ok(&self.buffer[0..out_pos])
     }
+
+    /// Compresses `input` directly into the caller-owned `out` slice with no
+    /// intermediate allocation, returning the number of bytes written. Errors if
+    /// `out` is too small to hold the compressed block.
+    pub fn compress_into(&self, input: &[u8], out: &mut [u8]) -> Result<usize> {
+        let mut compressor = zstd::bulk::Compressor::with_prepared_dictionary(&self.c_dict)?;
+        Ok(compressor.compress_to_buffer(input, out)?)
+    }
+
+    /// Decompresses `input` directly into the caller-owned `out` slice with no
+    /// intermediate allocation, returning the number of bytes written. Errors if
+    /// `out` is too small to hold the decompressed block.
+    pub fn decompress_into(&self, input: &[u8], out: &mut [u8]) -> Result<usize> {
+        let mut decompressor = zstd::bulk::Decompressor::with_prepared_dictionary(&self.d_dict)?;
+        Ok(decompressor.decompress_to_buffer(input, out)?)
+    }
+
+    /// Estimates the capacity to reserve for a decompressed block, reading the
+    /// decompressed size straight out of the zstd frame header so `decompress`/
+    /// `decompress_owned` don't repeatedly reallocate as the output grows past the
+    /// *compressed* length they used to reserve. Falls back to a growth heuristic
+    /// when the frame omits the content size (e.g. a streamed/unknown-size frame).
+    ///
+    /// The header's content size is attacker-controlled for blocks received over
+    /// the wire, so it is capped at an absolute ceiling before it's used to
+    /// reserve memory -- otherwise a tiny frame advertising a multi-gigabyte size
+    /// could force an oversized allocation. The ceiling is deliberately not a
+    /// ratio of the compressed length: legitimately high-ratio frames (e.g. a
+    /// near-constant multi-megabyte block) are exactly the case this hint exists
+    /// to serve.
+    fn decompressed_capacity_hint(bytes: &[u8]) -> usize {
+        const MAX_CAPACITY: usize = 256 * 1024 * 1024;
+
+        let fallback = bytes.len().saturating_mul(2);
+        let hint = match zstd::zstd_safe::get_frame_content_size(bytes) {
+            Ok(Some(size)) => size as usize,
+            _ => fallback,
+        };
+        hint.min(MAX_CAPACITY)
+    }
+
+    /// Wraps `r` in a streaming zstd decoder bound to this wrapper's dictionary, so
+    /// a batch of blocks can be decompressed straight from a socket or file without
+    /// holding the whole payload in memory.
+    pub fn reader<'a, R: io::Read + 'a>(&'a self, r: R) -> Result<impl io::Read + 'a> {
+        Ok(zstd::stream::Decoder::with_prepared_dictionary(r, &self.d_dict)?)
+    }
+
+    /// Wraps `w` in a streaming zstd encoder bound to this wrapper's dictionary, so
+    /// a batch of blocks can be compressed straight to a socket or file without
+    /// holding the whole payload in memory. The returned writer finishes the zstd
+    /// frame automatically when dropped.
+    pub fn writer<'a, W: io::Write + 'a>(
+        &'a self,
+        w: W,
+    ) -> Result<zstd::stream::AutoFinishEncoder<'a, W>> {
+        let encoder = zstd::stream::Encoder::with_prepared_dictionary(w, &self.c_dict)?;
+        Ok(encoder.auto_finish())
+    }
+}
+
+impl BlockCompressor for ZstdWrapper {
+    fn compress(&mut self, bytes: &[u8]) -> Result<&[u8]> {
+        ZstdWrapper::compress(self, bytes)
+    }
+
+    fn decompress(&mut self, bytes: &[u8]) -> Result<&[u8]> {
+        ZstdWrapper::decompress(self, bytes)
+    }
 }
 
 impl Default for ZstdWrapper {
@@ -82,6 +187,7 @@ impl Default for ZstdWrapper {
 
 #[cfg(test)]
 mod test {
+    use std::io;
     use std::io::Read;
 
     use rand::Rng;
@@ -182,4 +288,104 @@ mod test {
 
         assert_eq!(expected, got);
     }
+
+    #[test]
+    fn test_train_dictionary_roundtrip() {
+        // zstd's trainer rejects a corpus of only a few tiny samples (it holds
+        // part of the corpus back for evaluation), so build a varied one.
+        let corpus: Vec<Vec<u8>> = (0..128u32)
+            .map(|i| {
+                let fill = [b'a', b'b', b'c'][(i % 3) as usize];
+                let mut sample = b"asasaasasasasasasasasas".to_vec();
+                sample.extend(std::iter::repeat(fill).take(12));
+                sample.extend_from_slice(format!("asassas{i:08}").as_bytes());
+                sample.repeat(2)
+            })
+            .collect();
+        let samples: Vec<&[u8]> = corpus.iter().map(Vec::as_slice).collect();
+        let dict = ZstdWrapper::train_dictionary(&samples, 4096).unwrap();
+
+        let mut encoder = ZstdWrapper::with_dictionary(&dict, zstd::DEFAULT_COMPRESSION_LEVEL);
+        let input = b"asasaasasasasasasasasasddddddddddddasassas";
+        let res = encoder.compress(&input[..]).unwrap().to_vec();
+        let got = encoder.decompress(&res).unwrap();
+
+        assert_eq!(&got, input);
+    }
+
+    #[test]
+    fn test_compress_decompress_into() {
+        let encoder = ZstdWrapper::new();
+        let input = b"asasaasasasasasasasasasaaaaaaaaaaaaasassas";
+
+        let mut compressed = vec![0u8; 1024];
+        let compressed_len = encoder.compress_into(&input[..], &mut compressed).unwrap();
+
+        let mut decompressed = vec![0u8; input.len()];
+        let decompressed_len = encoder
+            .decompress_into(&compressed[..compressed_len], &mut decompressed)
+            .unwrap();
+
+        assert_eq!(&decompressed[..decompressed_len], input);
+    }
+
+    #[test]
+    fn test_compress_into_buffer_too_small() {
+        let encoder = ZstdWrapper::new();
+        let input = b"asasaasasasasasasasasasaaaaaaaaaaaaasassas";
+
+        let mut compressed = vec![0u8; 1];
+        assert!(encoder.compress_into(&input[..], &mut compressed).is_err());
+    }
+
+    #[test]
+    fn test_decompressed_capacity_hint_reads_frame_header() {
+        // A frame that truthfully advertises a small content size should be
+        // trusted as-is.
+        let encoder = ZstdWrapper::new();
+        let small = encoder.compress_owned(&[0u8; 1024]).unwrap();
+        assert_eq!(ZstdWrapper::decompressed_capacity_hint(&small), 1024);
+
+        // A legitimately high-ratio block, like the constant 8 MiB block in
+        // `bench_encode_8mb`, should still get an exact hint -- the point of
+        // reading the frame header is to avoid reallocating on exactly this
+        // case, so it must not be clamped away by a ratio heuristic.
+        let huge = vec![8u8; 8 * 1024 * 1024];
+        let compressed = encoder.compress_owned(&huge).unwrap();
+        assert_eq!(
+            ZstdWrapper::decompressed_capacity_hint(&compressed),
+            huge.len()
+        );
+    }
+
+    #[test]
+    fn test_decompressed_capacity_hint_caps_attacker_controlled_size() {
+        // A tiny forged "frame" cannot claim to decompress to more than the
+        // absolute ceiling, regardless of how small the compressed bytes are.
+        // Magic number, a descriptor with an 8-byte content size field, a
+        // window descriptor, then a content size of 1 TiB.
+        let mut forged = vec![0x28, 0xB5, 0x2F, 0xFD, 0xC0, 0x00];
+        forged.extend_from_slice(&(1u64 << 40).to_le_bytes());
+        assert!(ZstdWrapper::decompressed_capacity_hint(&forged) <= 256 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_reader_writer_roundtrip() {
+        let encoder = ZstdWrapper::new();
+        let input = b"asasaasasasasasasasasasaaaaaaaaaaaaasassas".repeat(100);
+
+        let mut compressed = Vec::new();
+        {
+            let mut writer = encoder.writer(&mut compressed).unwrap();
+            io::copy(&mut io::Cursor::new(&input), &mut writer).unwrap();
+            // `writer` finishes the zstd frame automatically when it's dropped
+            // at the end of this block.
+        }
+
+        let mut decompressed = Vec::new();
+        let mut reader = encoder.reader(io::Cursor::new(&compressed)).unwrap();
+        reader.read_to_end(&mut decompressed).unwrap();
+
+        assert_eq!(decompressed, input);
+    }
 }
@@ -0,0 +1,75 @@
+use std::io;
+
+use anyhow::Result;
+
+use crate::codec::BlockCompressor;
+
+/// LZ4 codec backend: lower compression ratio than [`crate::ZstdWrapper`], much
+/// lower CPU cost.
+pub struct Lz4Wrapper {
+    buffer: Vec<u8>,
+}
+
+impl Lz4Wrapper {
+    pub fn new() -> Self {
+        Self { buffer: Vec::new() }
+    }
+
+    pub fn compress(&mut self, bytes: &[u8]) -> Result<&[u8]> {
+        self.buffer.truncate(0);
+        let mut wrapper = io::Cursor::new(bytes);
+        let mut output_wrapper = io::Cursor::new(&mut self.buffer);
+
+        let mut encoder = ::lz4::EncoderBuilder::new().build(&mut output_wrapper)?;
+        io::copy(&mut wrapper, &mut encoder)?;
+        let (_, result) = encoder.finish();
+        result?;
+
+        let out_pos = output_wrapper.position() as usize;
+        drop(output_wrapper);
+        Ok(&self.buffer[0..out_pos])
+    }
+
+    pub fn decompress(&mut self, bytes: &[u8]) -> Result<&[u8]> {
+        self.buffer.truncate(0);
+        let mut wrapper = io::Cursor::new(bytes);
+        let mut output_wrapper = io::Cursor::new(&mut self.buffer);
+
+        let mut decoder = ::lz4::Decoder::new(&mut wrapper)?;
+        io::copy(&mut decoder, &mut output_wrapper)?;
+
+        let out_pos = output_wrapper.position() as usize;
+        drop(output_wrapper);
+        Ok(&self.buffer[0..out_pos])
+    }
+}
+
+impl Default for Lz4Wrapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockCompressor for Lz4Wrapper {
+    fn compress(&mut self, bytes: &[u8]) -> Result<&[u8]> {
+        Lz4Wrapper::compress(self, bytes)
+    }
+
+    fn decompress(&mut self, bytes: &[u8]) -> Result<&[u8]> {
+        Lz4Wrapper::decompress(self, bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Lz4Wrapper;
+
+    #[test]
+    fn test_encode() {
+        let mut encoder = Lz4Wrapper::new();
+        let input = b"asasaasasasasasasasasasaaaaaaaaaaaaasassas";
+        let res = encoder.compress(&input[..]).unwrap().to_vec();
+        let got = encoder.decompress(&res).unwrap();
+        assert_eq!(got, input);
+    }
+}